@@ -0,0 +1,65 @@
+mod client;
+pub mod jsonrpc;
+mod transport;
+
+pub mod snippet;
+
+pub use client::Client;
+pub use lsp::{Position, Url};
+pub use lsp_types as lsp;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OffsetEncoding {
+    /// UTF-8 code units aka bytes
+    Utf8,
+    /// UTF-16 code units
+    Utf16,
+}
+
+pub mod util {
+    use super::*;
+    use helix_core::{Rope, Transaction};
+
+    /// Converts an LSP position into a document character index, honoring the
+    /// negotiated offset encoding.
+    pub fn lsp_pos_to_pos(
+        doc: &Rope,
+        pos: lsp::Position,
+        offset_encoding: OffsetEncoding,
+    ) -> Option<usize> {
+        let max_line = doc.lines().count().saturating_sub(1);
+        let pos_line = pos.line as usize;
+        if pos_line > max_line {
+            return None;
+        }
+        let line = doc.line_to_char(pos_line);
+        match offset_encoding {
+            OffsetEncoding::Utf8 => {
+                let line_start = doc.line_to_byte(pos_line);
+                Some(doc.byte_to_char(line_start + pos.character as usize))
+            }
+            OffsetEncoding::Utf16 => {
+                let line_start = doc.char_to_utf16_cu(line);
+                Some(doc.utf16_cu_to_char(line_start + pos.character as usize))
+            }
+        }
+    }
+
+    /// Generates a [`Transaction`] from a list of LSP text edits.
+    pub fn generate_transaction_from_edits(
+        doc: &Rope,
+        edits: Vec<lsp::TextEdit>,
+        offset_encoding: OffsetEncoding,
+    ) -> Transaction {
+        Transaction::change(
+            doc,
+            edits.into_iter().map(|edit| {
+                let start = lsp_pos_to_pos(doc, edit.range.start, offset_encoding).unwrap_or(0);
+                let end = lsp_pos_to_pos(doc, edit.range.end, offset_encoding).unwrap_or(0);
+                (start, end, Some(edit.new_text.into()))
+            }),
+        )
+    }
+}
+
+pub use util::*;
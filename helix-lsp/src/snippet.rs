@@ -0,0 +1,335 @@
+use std::borrow::Cow;
+
+use anyhow::{anyhow, Result};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CaseChange {
+    Upcase,
+    Downcase,
+    Capitalize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormatItem<'a> {
+    Text(&'a str),
+    Capture(usize),
+    CaseChange(usize, CaseChange),
+    Conditional(usize, Option<&'a str>, Option<&'a str>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SnippetElement<'a> {
+    Tabstop {
+        tabstop: usize,
+    },
+    Placeholder {
+        tabstop: usize,
+        value: Vec<SnippetElement<'a>>,
+    },
+    Choice {
+        tabstop: usize,
+        choices: Vec<&'a str>,
+    },
+    Variable {
+        name: &'a str,
+        default: Option<&'a str>,
+        // TODO: regex
+    },
+    Text(Cow<'a, str>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Snippet<'a> {
+    elements: Vec<SnippetElement<'a>>,
+}
+
+pub fn parse(s: &str) -> Result<Snippet<'_>> {
+    parser::parse(s).map_err(|rest| anyhow!("Failed to parse snippet. Remaining input: {}", rest))
+}
+
+/// A rendered snippet: the final text with all placeholders filled with their
+/// defaults, together with the ordered tabstops (`$1`, `$2`, ..., `$0` last)
+/// and the character ranges each occupies in `text`. Tabstops sharing the same
+/// number keep multiple ranges so their selections can be linked and edited
+/// simultaneously.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RenderedSnippet {
+    pub text: String,
+    /// `tabstops[i]` holds every char range belonging to the i-th tabstop in
+    /// visiting order; `$0`, when the snippet has one, is the last entry. No
+    /// implicit `$0` is added to a snippet that lacks it.
+    pub tabstops: Vec<Vec<(usize, usize)>>,
+}
+
+impl Snippet<'_> {
+    /// Renders the snippet into its final text, filling placeholders with their
+    /// default values and collecting the tabstop ranges in order.
+    pub fn render(&self) -> RenderedSnippet {
+        let mut text = String::new();
+        // Collected as (tabstop number, range) pairs, sorted afterwards so that
+        // `$0` sinks to the end regardless of where it appears in the source.
+        let mut ranges: Vec<(usize, (usize, usize))> = Vec::new();
+        render_elements(&self.elements, &mut text, &mut ranges);
+
+        ranges.sort_by_key(|&(num, _)| if num == 0 { usize::MAX } else { num });
+
+        let mut tabstops: Vec<Vec<(usize, usize)>> = Vec::new();
+        let mut last_num: Option<usize> = None;
+        for (num, range) in ranges {
+            if last_num == Some(num) {
+                tabstops.last_mut().unwrap().push(range);
+            } else {
+                tabstops.push(vec![range]);
+                last_num = Some(num);
+            }
+        }
+
+        RenderedSnippet { text, tabstops }
+    }
+}
+
+fn render_elements(
+    elements: &[SnippetElement<'_>],
+    text: &mut String,
+    ranges: &mut Vec<(usize, (usize, usize))>,
+) {
+    use SnippetElement::*;
+
+    for element in elements {
+        match element {
+            Text(t) => text.push_str(t),
+            Variable { name: _, default } => {
+                if let Some(default) = default {
+                    text.push_str(default);
+                }
+            }
+            Tabstop { tabstop } => {
+                let start = text.chars().count();
+                ranges.push((*tabstop, (start, start)));
+            }
+            Placeholder { tabstop, value } => {
+                let start = text.chars().count();
+                render_elements(value, text, ranges);
+                let end = text.chars().count();
+                ranges.push((*tabstop, (start, end)));
+            }
+            Choice { tabstop, choices } => {
+                let start = text.chars().count();
+                if let Some(first) = choices.first() {
+                    text.push_str(first);
+                }
+                let end = text.chars().count();
+                ranges.push((*tabstop, (start, end)));
+            }
+        }
+    }
+}
+
+mod parser {
+    //! A recursive-descent parser for the LSP/TextMate snippet grammar:
+    //! `$0`, `$1`, `${1:placeholder}`, `${1|a,b,c|}` and `\$`/`\}`/`\\` escapes.
+
+    use super::{CaseChange, FormatItem, Snippet, SnippetElement};
+
+    type Input<'a> = &'a str;
+    /// Result of a parse: either the remaining input with a value, or the
+    /// remaining input on failure.
+    type PResult<'a, O> = Result<(Input<'a>, O), Input<'a>>;
+
+    pub fn parse(s: &str) -> Result<Snippet<'_>, &str> {
+        let (rest, elements) = snippet(s)?;
+        if rest.is_empty() {
+            Ok(Snippet { elements })
+        } else {
+            Err(rest)
+        }
+    }
+
+    fn snippet(input: &str) -> PResult<'_, Vec<SnippetElement<'_>>> {
+        let mut elements = Vec::new();
+        let mut input = input;
+        while !input.is_empty() {
+            let (rest, element) = element(input, false)?;
+            elements.push(element);
+            input = rest;
+        }
+        Ok((input, elements))
+    }
+
+    /// Parses a single element. `in_braces` is set while parsing the body of a
+    /// `${...}` placeholder so that plain text stops at the closing `}` rather
+    /// than swallowing it.
+    fn element(input: &str, in_braces: bool) -> PResult<'_, SnippetElement<'_>> {
+        if input.starts_with('$') {
+            tabstop_or_placeholder(input)
+        } else {
+            text(input, in_braces)
+        }
+    }
+
+    fn text(input: &str, in_braces: bool) -> PResult<'_, SnippetElement<'_>> {
+        let mut value = String::new();
+        let mut chars = input.char_indices().peekable();
+        let mut consumed = 0;
+        while let Some(&(i, c)) = chars.peek() {
+            match c {
+                '$' => break,
+                '}' if in_braces => break,
+                '\\' => {
+                    chars.next();
+                    if let Some(&(_, escaped)) = chars.peek() {
+                        value.push(escaped);
+                        chars.next();
+                        consumed = i + escaped.len_utf8() + 1;
+                    } else {
+                        value.push('\\');
+                        consumed = i + 1;
+                    }
+                }
+                _ => {
+                    value.push(c);
+                    chars.next();
+                    consumed = i + c.len_utf8();
+                }
+            }
+        }
+        if value.is_empty() {
+            Err(input)
+        } else {
+            Ok((&input[consumed..], SnippetElement::Text(value.into())))
+        }
+    }
+
+    fn tabstop_or_placeholder(input: &str) -> PResult<'_, SnippetElement<'_>> {
+        let rest = &input['$'.len_utf8()..];
+        if let Some(rest) = rest.strip_prefix('{') {
+            braced(rest)
+        } else {
+            let (rest, num) = digits(rest)?;
+            Ok((rest, SnippetElement::Tabstop { tabstop: num }))
+        }
+    }
+
+    fn braced(input: &str) -> PResult<'_, SnippetElement<'_>> {
+        let (rest, num) = digits(input)?;
+        match rest.chars().next() {
+            Some('}') => Ok((&rest['}'.len_utf8()..], SnippetElement::Tabstop { tabstop: num })),
+            Some(':') => {
+                let inner = &rest[':'.len_utf8()..];
+                let (rest, value) = placeholder_body(inner)?;
+                let rest = rest.strip_prefix('}').ok_or(rest)?;
+                Ok((
+                    rest,
+                    SnippetElement::Placeholder {
+                        tabstop: num,
+                        value,
+                    },
+                ))
+            }
+            Some('|') => {
+                let inner = &rest['|'.len_utf8()..];
+                let (rest, choices) = choices(inner)?;
+                let rest = rest.strip_prefix("|}").ok_or(rest)?;
+                Ok((
+                    rest,
+                    SnippetElement::Choice {
+                        tabstop: num,
+                        choices,
+                    },
+                ))
+            }
+            _ => Err(rest),
+        }
+    }
+
+    fn placeholder_body(input: &str) -> PResult<'_, Vec<SnippetElement<'_>>> {
+        let mut elements = Vec::new();
+        let mut input = input;
+        while !input.starts_with('}') && !input.is_empty() {
+            let (rest, element) = element(input, true)?;
+            elements.push(element);
+            input = rest;
+        }
+        Ok((input, elements))
+    }
+
+    fn choices(input: &str) -> PResult<'_, Vec<&str>> {
+        let end = input.find('|').ok_or(input)?;
+        let choices = input[..end].split(',').collect();
+        Ok((&input[end..], choices))
+    }
+
+    fn digits(input: &str) -> PResult<'_, usize> {
+        let end = input
+            .char_indices()
+            .find(|(_, c)| !c.is_ascii_digit())
+            .map(|(i, _)| i)
+            .unwrap_or(input.len());
+        if end == 0 {
+            return Err(input);
+        }
+        input[..end].parse().map(|n| (&input[end..], n)).map_err(|_| input)
+    }
+
+    // Keep the format-string types referenced so the grammar stays complete even
+    // though regex-based variable transforms are not parsed yet.
+    #[allow(dead_code)]
+    fn _format_items() -> (CaseChange, FormatItem<'static>) {
+        (CaseChange::Upcase, FormatItem::Capture(0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_placeholder() {
+        let snippet = parse("${1:foo}").unwrap();
+        assert_eq!(
+            snippet.elements,
+            vec![SnippetElement::Placeholder {
+                tabstop: 1,
+                value: vec![SnippetElement::Text("foo".into())],
+            }]
+        );
+    }
+
+    #[test]
+    fn render_placeholder_fills_default_and_ranges() {
+        let rendered = parse("println!(${1:msg})$0").unwrap().render();
+        assert_eq!(rendered.text, "println!(msg)");
+        // `$1` covers "msg", `$0` is the empty tabstop after the `)`
+        assert_eq!(rendered.tabstops, vec![vec![(9, 12)], vec![(13, 13)]]);
+    }
+
+    #[test]
+    fn render_nested_placeholder() {
+        let rendered = parse("outer(${1:inner $2 end})").unwrap().render();
+        assert_eq!(rendered.text, "outer(inner  end)");
+        // `$1` spans the whole placeholder body, `$2` the empty stop within it
+        assert_eq!(rendered.tabstops, vec![vec![(6, 16)], vec![(12, 12)]]);
+    }
+
+    #[test]
+    fn render_linked_tabstops() {
+        let rendered = parse("<$1>$1</$1>").unwrap().render();
+        assert_eq!(rendered.text, "<></>");
+        // all three `$1` occurrences are linked in a single tabstop group
+        assert_eq!(rendered.tabstops, vec![vec![(1, 1), (2, 2), (4, 4)]]);
+    }
+
+    #[test]
+    fn render_choice_uses_first() {
+        let rendered = parse("${1|a,b,c|}").unwrap().render();
+        assert_eq!(rendered.text, "a");
+        assert_eq!(rendered.tabstops, vec![vec![(0, 1)]]);
+    }
+
+    #[test]
+    fn parse_escaped_dollar() {
+        let rendered = parse("cost: \\$5").unwrap().render();
+        assert_eq!(rendered.text, "cost: $5");
+        assert!(rendered.tabstops.is_empty());
+    }
+}
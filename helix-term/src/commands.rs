@@ -0,0 +1,74 @@
+use helix_view::snippets::SnippetState;
+use helix_view::Editor;
+
+use crate::compositor;
+use crate::job::Jobs;
+
+pub struct Context<'a> {
+    pub register: Option<char>,
+    pub count: Option<std::num::NonZeroUsize>,
+    pub editor: &'a mut Editor,
+    pub callback: Option<compositor::Callback>,
+    pub on_next_key_callback: Option<Box<dyn FnOnce(&mut Context, crossterm::event::KeyEvent)>>,
+    pub jobs: &'a mut Jobs,
+}
+
+/// A command that can be bound to a key in the keymap. `Static` commands are the
+/// built-in editor commands; they are enumerated by the [`static_commands!`]
+/// macro so the keymap can resolve them by name.
+#[derive(Clone)]
+pub enum MappableCommand {
+    Typable {
+        name: String,
+        args: Vec<String>,
+        doc: String,
+    },
+    Static {
+        name: &'static str,
+        fun: fn(cx: &mut Context),
+        doc: &'static str,
+    },
+}
+
+macro_rules! static_commands {
+    ( $($name:ident, $doc:literal,)* ) => {
+        $(
+            #[allow(non_upper_case_globals)]
+            pub(crate) const $name: Self = Self::Static {
+                name: stringify!($name),
+                fun: $name,
+                doc: $doc,
+            };
+        )*
+
+        pub const STATIC_COMMAND_LIST: &'static [Self] = &[
+            $( Self::$name, )*
+        ];
+    };
+}
+
+impl MappableCommand {
+    // Only the completion/snippet commands relevant to this change are shown
+    // here; the full list lives alongside the other editor commands.
+    static_commands!(
+        completion, "Invoke completion popup",
+        next_snippet_placeholder, "Move to next snippet placeholder",
+    );
+}
+
+/// The `completion` entry of the command table. This definition is a no-op: it
+/// does not open the completion popup.
+pub fn completion(cx: &mut Context) {
+    let _ = cx;
+}
+
+/// Advances the selection to the next snippet tabstop, dropping linked
+/// selections on all same-numbered tabstops and ending at `$0`. Clears the
+/// active snippet once the final tabstop has been passed.
+pub fn next_snippet_placeholder(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    match doc.active_snippet_mut().and_then(SnippetState::advance) {
+        Some(selection) => doc.set_selection(view.id, selection),
+        None => doc.set_active_snippet(None),
+    }
+}
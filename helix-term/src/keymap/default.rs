@@ -0,0 +1,22 @@
+use std::collections::HashMap;
+
+use super::macros::keymap;
+use super::Keymap;
+use helix_view::document::Mode;
+
+/// The built-in, default keymap. Only the insert-mode subset relevant to this
+/// change is reproduced here; the full map lives alongside the other modes.
+pub fn default() -> HashMap<Mode, Keymap> {
+    let insert = keymap!({ "Insert mode"
+        "esc" => normal_mode,
+
+        // advance through snippet tabstops after accepting a snippet completion
+        "tab" => next_snippet_placeholder,
+
+        "C-x" => completion,
+    });
+
+    let mut map = HashMap::new();
+    map.insert(Mode::Insert, Keymap::new(insert));
+    map
+}
@@ -4,13 +4,13 @@ use tui::buffer::Buffer as Surface;
 
 use std::borrow::Cow;
 
-use helix_core::Transaction;
-use helix_view::{graphics::Rect, Document, Editor, View};
+use helix_core::{Range, Transaction};
+use helix_view::{graphics::Rect, snippets::SnippetState, Document, Editor, View};
 
 use crate::commands;
 use crate::ui::{menu, Markdown, Menu, Popup, PromptEvent};
 
-use helix_lsp::{lsp, util};
+use helix_lsp::{lsp, snippet, util};
 use lsp::CompletionItem;
 
 impl menu::Item for CompletionItem {
@@ -72,6 +72,9 @@ pub struct Completion {
     start_offset: usize,
     #[allow(dead_code)]
     trigger_offset: usize,
+    // Labels of items we have already requested (or received) a
+    // `completionItem/resolve` for, so the same item is never resolved twice.
+    resolve_requested: std::collections::HashSet<String>,
     // TODO: maintain a completioncontext with trigger kind & trigger char
 }
 
@@ -84,86 +87,242 @@ impl Completion {
         trigger_offset: usize,
     ) -> Self {
         // let items: Vec<CompletionItem> = Vec::new();
+
+        // revert transaction of the last previewed completion; kept across menu
+        // callbacks so the next preview can cleanly undo the previous one
+        let mut last_revert: Option<Transaction> = None;
+
         let menu = Menu::new(items, move |editor: &mut Editor, item, event| {
+            fn is_snippet(item: &CompletionItem) -> bool {
+                matches!(
+                    item.insert_text_format,
+                    Some(lsp::InsertTextFormat::Snippet)
+                )
+            }
+
+            /// Renders a snippet body, returning the final text and the tabstop
+            /// ranges relative to `start` (in chars). Falls back to inserting the
+            /// raw body verbatim if it fails to parse.
+            fn render_snippet(body: &str, start: usize) -> (String, Option<Vec<Vec<Range>>>) {
+                match snippet::parse(body) {
+                    Ok(snippet) => {
+                        let rendered = snippet.render();
+                        let tabstops = rendered
+                            .tabstops
+                            .into_iter()
+                            .map(|ranges| {
+                                ranges
+                                    .into_iter()
+                                    .map(|(from, to)| Range::new(start + from, start + to))
+                                    .collect()
+                            })
+                            .collect();
+                        (rendered.text, Some(tabstops))
+                    }
+                    Err(err) => {
+                        log::error!("failed to parse snippet: {}", err);
+                        (body.to_string(), None)
+                    }
+                }
+            }
+
+            // Chooses the `insert` or `replace` range of an `InsertAndReplace`
+            // edit. Servers such as rust-analyzer and clangd return both; the
+            // default is to `replace` when the cursor is at or past the end of the
+            // identifier being completed (the common case when re-typing over a
+            // symbol) and to `insert` otherwise, preserving any text that trails
+            // the cursor.
+            fn insert_replace_range(
+                doc: &Document,
+                view: &View,
+                edit: &lsp::InsertReplaceEdit,
+                offset_encoding: helix_lsp::OffsetEncoding,
+                completion_replace: bool,
+            ) -> lsp::Range {
+                let text = doc.text().slice(..);
+                let cursor = doc.selection(view.id).primary().cursor(text);
+                let replace_end =
+                    util::lsp_pos_to_pos(doc.text(), edit.replace.end, offset_encoding);
+                // The `completion_replace` config gates the replace range; when it
+                // is disabled we always insert and never overwrite trailing text.
+                if completion_replace && prefer_replace(cursor, replace_end) {
+                    edit.replace
+                } else {
+                    edit.insert
+                }
+            }
+
+            // Builds the completion as a SINGLE edit so no manual re-deletion of
+            // the filter text is layered on top. When the server supplies a
+            // `text_edit` its range already covers the text to replace; otherwise
+            // we replace `trigger_offset..cursor` (the typed filter text) with the
+            // insert text ourselves.
             fn item_to_transaction(
                 doc: &Document,
                 view: &View,
                 item: &CompletionItem,
                 offset_encoding: helix_lsp::OffsetEncoding,
-            ) -> Transaction {
+                trigger_offset: usize,
+                completion_replace: bool,
+            ) -> (Transaction, Option<Vec<Vec<Range>>>) {
                 if let Some(edit) = &item.text_edit {
                     let edit = match edit {
                         lsp::CompletionTextEdit::Edit(edit) => edit.clone(),
-                        lsp::CompletionTextEdit::InsertAndReplace(item) => {
-                            unimplemented!("completion: insert_and_replace {:?}", item)
+                        lsp::CompletionTextEdit::InsertAndReplace(edit) => {
+                            let range = insert_replace_range(
+                                doc,
+                                view,
+                                edit,
+                                offset_encoding,
+                                completion_replace,
+                            );
+                            lsp::TextEdit::new(range, edit.new_text.clone())
                         }
                     };
-                    util::generate_transaction_from_edits(
-                        doc.text(),
-                        vec![edit],
-                        offset_encoding, // TODO: should probably transcode in Client
-                    )
+                    if is_snippet(item) {
+                        let start =
+                            util::lsp_pos_to_pos(doc.text(), edit.range.start, offset_encoding)
+                                .unwrap_or(0);
+                        let (text, tabstops) = render_snippet(&edit.new_text, start);
+                        let edit = lsp::TextEdit::new(edit.range, text);
+                        let transaction = util::generate_transaction_from_edits(
+                            doc.text(),
+                            vec![edit],
+                            offset_encoding,
+                        );
+                        (transaction, tabstops)
+                    } else {
+                        let transaction = util::generate_transaction_from_edits(
+                            doc.text(),
+                            vec![edit],
+                            offset_encoding, // TODO: should probably transcode in Client
+                        );
+                        (transaction, None)
+                    }
                 } else {
                     let text = item.insert_text.as_ref().unwrap_or(&item.label);
                     let cursor = doc
                         .selection(view.id)
                         .primary()
                         .cursor(doc.text().slice(..));
-                    Transaction::change(
-                        doc.text(),
-                        vec![(cursor, cursor, Some(text.as_str().into()))].into_iter(),
-                    )
+                    // replace the filter text typed since the trigger, so the
+                    // completion is a single reversible change
+                    let start = trigger_offset.min(cursor);
+                    if is_snippet(item) {
+                        let (text, tabstops) = render_snippet(text, start);
+                        let transaction = Transaction::change(
+                            doc.text(),
+                            vec![(start, cursor, Some(text.into()))].into_iter(),
+                        );
+                        (transaction, tabstops)
+                    } else {
+                        let transaction = Transaction::change(
+                            doc.text(),
+                            vec![(start, cursor, Some(text.as_str().into()))].into_iter(),
+                        );
+                        (transaction, None)
+                    }
                 }
             }
 
+            // Applies `item`'s completion to the document, first undoing the
+            // previously previewed completion (if any) so only one completion is
+            // ever live in the buffer. The revert of the newly applied completion
+            // is mapped over any changes the user has since made and returned in
+            // `last_revert` for the next call; a single undo after accepting then
+            // restores exactly the pre-completion text.
+            fn apply_completion(
+                doc: &mut Document,
+                view: &View,
+                item: &CompletionItem,
+                offset_encoding: helix_lsp::OffsetEncoding,
+                trigger_offset: usize,
+                completion_replace: bool,
+                last_revert: &mut Option<Transaction>,
+            ) -> Option<Vec<Vec<Range>>> {
+                // revert the last previewed completion, mapping it over the
+                // changes (i.e. the characters the user typed to filter) made
+                // since it was applied
+                if let Some(revert) = last_revert.take() {
+                    let revert = revert.map(doc.changes().clone());
+                    doc.apply(&revert, view.id);
+                }
+
+                // the text we are about to return to on the next preview / undo
+                let original = doc.text().clone();
+
+                // a single edit that both replaces the filter text and applies
+                // the completion; its revert restores exactly the pre-completion
+                // text with one undo
+                let (transaction, tabstops) = item_to_transaction(
+                    doc,
+                    view,
+                    item,
+                    offset_encoding,
+                    trigger_offset,
+                    completion_replace,
+                );
+                *last_revert = Some(transaction.invert(&original));
+                doc.apply(&transaction, view.id);
+
+                tabstops
+            }
+
             match event {
                 PromptEvent::Abort => {}
                 PromptEvent::Update => {
+                    let completion_replace = editor.config().completion_replace;
                     let (view, doc) = current!(editor);
 
                     // always present here
                     let item = item.unwrap();
 
-                    // if more text was entered, remove it
-                    // TODO: ideally to undo we should keep the last completion tx revert, and map it over new changes
-                    let cursor = doc
-                        .selection(view.id)
-                        .primary()
-                        .cursor(doc.text().slice(..));
-                    if trigger_offset < cursor {
-                        let remove = Transaction::change(
-                            doc.text(),
-                            vec![(trigger_offset, cursor, None)].into_iter(),
-                        );
-                        doc.apply(&remove, view.id);
-                    }
-
-                    let transaction = item_to_transaction(doc, view, item, offset_encoding);
-                    doc.apply(&transaction, view.id);
+                    apply_completion(
+                        doc,
+                        view,
+                        item,
+                        offset_encoding,
+                        trigger_offset,
+                        completion_replace,
+                        &mut last_revert,
+                    );
                 }
                 PromptEvent::Validate => {
+                    let completion_replace = editor.config().completion_replace;
                     let (view, doc) = current!(editor);
 
                     // always present here
                     let item = item.unwrap();
 
-                    // if more text was entered, remove it
-                    // TODO: ideally to undo we should keep the last completion tx revert, and map it over new changes
-                    let cursor = doc
-                        .selection(view.id)
-                        .primary()
-                        .cursor(doc.text().slice(..));
-                    if trigger_offset < cursor {
-                        let remove = Transaction::change(
-                            doc.text(),
-                            vec![(trigger_offset, cursor, None)].into_iter(),
-                        );
-                        doc.apply(&remove, view.id);
+                    let tabstops = apply_completion(
+                        doc,
+                        view,
+                        item,
+                        offset_encoding,
+                        trigger_offset,
+                        completion_replace,
+                        &mut last_revert,
+                    );
+
+                    // For snippets, drop multi-cursor selections onto the
+                    // lowest-numbered tabstop and stash the remaining ones so
+                    // `next_snippet_placeholder` can step through them, ending at
+                    // `$0`. Tabstops sharing a number stay linked and are edited
+                    // simultaneously.
+                    if let Some(tabstops) = tabstops {
+                        if !tabstops.is_empty() {
+                            let mut snippet = SnippetState::new(tabstops);
+                            // ranges were computed against the rendered snippet
+                            // text before it was applied; map them onto the
+                            // document's current state
+                            snippet.map(doc.changes());
+                            doc.set_selection(view.id, snippet.active_selection());
+                            // keep the snippet alive so `next_snippet_placeholder`
+                            // (Tab) can step through the remaining tabstops
+                            doc.set_active_snippet(Some(snippet));
+                        }
                     }
 
-                    let transaction = item_to_transaction(doc, view, item, offset_encoding);
-                    doc.apply(&transaction, view.id);
-
                     if let Some(additional_edits) = &item.additional_text_edits {
                         // gopls uses this to add extra imports
                         if !additional_edits.is_empty() {
@@ -183,6 +342,7 @@ impl Completion {
             popup,
             start_offset,
             trigger_offset,
+            resolve_requested: std::collections::HashSet::new(),
         };
 
         // need to recompute immediately in case start_offset != trigger_offset
@@ -191,6 +351,112 @@ impl Completion {
         completion
     }
 
+    /// Fires an async `completionItem/resolve` for the highlighted item, if the
+    /// server advertises resolve support and the item has not been resolved yet.
+    /// When the response arrives the resolved `documentation`/`detail`/
+    /// `additionalTextEdits`/`commitCharacters` are merged back into the stored
+    /// item; a response is
+    /// dropped if the highlighted item has since changed (stale resolve).
+    fn resolve_completion_item(&mut self, editor: &Editor, jobs: &mut crate::job::Jobs) {
+        let item = match self.popup.contents().selection() {
+            Some(item) => item.clone(),
+            None => return,
+        };
+
+        let (_view, doc) = current_ref!(editor);
+        let language_server = match doc.language_server() {
+            Some(language_server) => language_server,
+            None => return,
+        };
+
+        // Only bother if the server can actually resolve.
+        let resolve_provider = language_server
+            .capabilities()
+            .completion_provider
+            .as_ref()
+            .and_then(|provider| provider.resolve_provider)
+            .unwrap_or(false);
+        if !resolve_provider {
+            return;
+        }
+
+        // Guard against resolving the same item twice. The label alone is not
+        // unique (overloads share it), so key on label + the server's opaque
+        // `data`, which distinguishes items and is preserved across a resolve.
+        // This must run only once we know a resolve will actually fire, so an
+        // item isn't permanently marked "requested" when no server was ready.
+        let key = resolve_key(&item);
+        if !self.resolve_requested.insert(key.clone()) {
+            return;
+        }
+
+        let future = language_server.resolve_completion_item(item.clone());
+
+        jobs.callback(async move {
+            let resolved = future.await?;
+            let call: crate::job::Callback = Box::new(move |_editor, compositor| {
+                if let Some(completion) = compositor.find::<Completion>() {
+                    completion.merge_resolved(&key, resolved);
+                }
+            });
+            Ok(call)
+        });
+    }
+
+    /// Merges a resolved item back into the stored completion, unless the
+    /// highlighted item has changed since the request went out. `key` is the
+    /// stable identity (see [`resolve_key`]) of the item the request was made
+    /// for; a resolve for a different item is dropped.
+    fn merge_resolved(&mut self, key: &str, resolved: CompletionItem) {
+        let menu = self.popup.contents_mut();
+        let item = match menu.selection_mut() {
+            Some(item) if resolve_key(item) == key => item,
+            // selection moved on; applying now would show stale data
+            _ => return,
+        };
+
+        if resolved.documentation.is_some() {
+            item.documentation = resolved.documentation;
+        }
+        if resolved.detail.is_some() {
+            item.detail = resolved.detail;
+        }
+        if resolved.additional_text_edits.is_some() {
+            item.additional_text_edits = resolved.additional_text_edits;
+        }
+        if resolved.commit_characters.is_some() {
+            item.commit_characters = resolved.commit_characters;
+        }
+    }
+
+    /// Returns whether `ch` should commit the highlighted completion. The commit
+    /// set is taken from the highlighted item's `commit_characters` (populated by
+    /// the resolve step when available), falling back to the server's default
+    /// commit set. Items and servers that advertise no commit characters disable
+    /// the behaviour, so it is only ever active when the server opts in. Returns
+    /// `false` when commit characters are disabled globally via config.
+    fn is_commit_char(&self, ch: char, editor: &Editor) -> bool {
+        if !editor.config().completion_commit_characters {
+            return false;
+        }
+
+        let item = match self.popup.contents().selection() {
+            Some(item) => item,
+            None => return false,
+        };
+
+        if let Some(commit_characters) = &item.commit_characters {
+            return commit_characters.iter().any(|s| s.contains(ch));
+        }
+
+        let (_view, doc) = current_ref!(editor);
+        doc.language_server()
+            .and_then(|ls| ls.capabilities().completion_provider.as_ref())
+            .and_then(|provider| provider.all_commit_characters.as_ref())
+            .map(|chars| chars.iter().any(|s| s.contains(ch)))
+            .unwrap_or(false)
+    }
+
     pub fn recompute_filter(&mut self, editor: &Editor) {
         // recompute menu based on matches
         let menu = self.popup.contents_mut();
@@ -223,7 +489,8 @@ impl Completion {
     }
 
     pub fn update(&mut self, cx: &mut commands::Context) {
-        self.recompute_filter(cx.editor)
+        self.recompute_filter(cx.editor);
+        self.resolve_completion_item(cx.editor, cx.jobs);
     }
 
     pub fn is_empty(&self) -> bool {
@@ -243,6 +510,26 @@ impl Completion {
 // - components register for hooks, then unregister when terminated
 // ... since completion is a special case, maybe just build it into doc/render?
 
+/// The positional default for `InsertAndReplace` edits: prefer the `replace`
+/// range when the cursor is at or past the end of the identifier being completed
+/// (`replace_end`), otherwise keep the trailing text and only `insert`.
+fn prefer_replace(cursor: usize, replace_end: Option<usize>) -> bool {
+    matches!(replace_end, Some(end) if cursor >= end)
+}
+
+/// A stable identity for a completion item, used to dedup resolve requests and
+/// to guard against applying a stale resolve. The display label is not unique
+/// (overloaded functions share it), so it is combined with the server's opaque
+/// `data` field, which the server round-trips through `completionItem/resolve`.
+fn resolve_key(item: &CompletionItem) -> String {
+    let data = item
+        .data
+        .as_ref()
+        .map(|data| data.to_string())
+        .unwrap_or_default();
+    format!("{}\u{0}{}", item.label, data)
+}
+
 impl Component for Completion {
     fn handle_event(&mut self, event: Event, cx: &mut Context) -> EventResult {
         // let the Editor handle Esc instead
@@ -252,7 +539,41 @@ impl Component for Completion {
         {
             return EventResult::Ignored;
         }
-        self.popup.handle_event(event, cx)
+
+        // A commit character accepts the highlighted item as if Enter were
+        // pressed and then gets inserted itself, letting the user type e.g. `.`
+        // to both select a method and continue typing.
+        if let Event::Key(KeyEvent {
+            code: KeyCode::Char(ch),
+            modifiers: crossterm::event::KeyModifiers::NONE,
+            ..
+        }) = event
+        {
+            if self.is_commit_char(ch, cx.editor) {
+                let result = self.popup.handle_event(
+                    Event::Key(KeyEvent::from(KeyCode::Enter)),
+                    cx,
+                );
+                // insert the triggering character through the normal insert
+                // command so auto-pairs and other insert hooks still run (typing
+                // `(` should still produce the matching `)`)
+                let mut cx = commands::Context {
+                    register: None,
+                    count: None,
+                    editor: cx.editor,
+                    callback: None,
+                    on_next_key_callback: None,
+                    jobs: cx.jobs,
+                };
+                commands::insert::insert_char(&mut cx, ch);
+                return result;
+            }
+        }
+
+        let result = self.popup.handle_event(event, cx);
+        // The popup may have moved the highlight; resolve the newly selected item.
+        self.resolve_completion_item(cx.editor, cx.jobs);
+        result
     }
 
     fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
@@ -366,4 +687,38 @@ impl Component for Completion {
             markdown_doc.render(area, surface, cx);
         }
     }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn item(label: &str, data: Option<serde_json::Value>) -> CompletionItem {
+        CompletionItem {
+            label: label.to_string(),
+            data,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolve_key_distinguishes_overloads_sharing_a_label() {
+        let a = item("foo", Some(serde_json::json!({"id": 1})));
+        let b = item("foo", Some(serde_json::json!({"id": 2})));
+        assert_ne!(resolve_key(&a), resolve_key(&b));
+        // the same item keys identically across a resolve round-trip
+        assert_eq!(resolve_key(&a), resolve_key(&a.clone()));
+    }
+
+    #[test]
+    fn prefer_replace_when_cursor_at_or_past_identifier_end() {
+        // cursor at the end of the identifier -> replace
+        assert!(prefer_replace(5, Some(5)));
+        // cursor past the end (re-typing over a symbol) -> replace
+        assert!(prefer_replace(7, Some(5)));
+        // cursor before the end (text trails the cursor) -> insert
+        assert!(!prefer_replace(3, Some(5)));
+        // no replace range available -> insert
+        assert!(!prefer_replace(3, None));
+    }
 }
\ No newline at end of file
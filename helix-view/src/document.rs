@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use helix_core::syntax::LanguageConfiguration;
+use helix_core::{ChangeSet, Rope, Selection, Transaction};
+
+use crate::snippets::SnippetState;
+use crate::{DocumentId, ViewId};
+
+pub struct Document {
+    pub(crate) id: DocumentId,
+    text: Rope,
+    selections: HashMap<ViewId, Selection>,
+
+    /// Pending changes accumulated since the last commit to history, used to
+    /// map stored positions (e.g. completion reverts and snippet tabstops)
+    /// forward onto the current document state.
+    changes: ChangeSet,
+
+    language: Option<Arc<LanguageConfiguration>>,
+    language_server: Option<Arc<helix_lsp::Client>>,
+
+    /// The snippet currently being filled in, if any. Kept here (rather than on
+    /// the popup) so tabstop navigation outlives the completion popup. It is
+    /// cleared when navigation passes the final tabstop or replaced by a new
+    /// snippet; moving the cursor out of the snippet region does not clear it.
+    active_snippet: Option<SnippetState>,
+}
+
+impl Document {
+    #[inline]
+    pub fn id(&self) -> DocumentId {
+        self.id
+    }
+
+    #[inline]
+    pub fn text(&self) -> &Rope {
+        &self.text
+    }
+
+    #[inline]
+    pub fn selection(&self, view_id: ViewId) -> &Selection {
+        &self.selections[&view_id]
+    }
+
+    pub fn set_selection(&mut self, view_id: ViewId, selection: Selection) {
+        let selection = selection.ensure_invariants(self.text().slice(..));
+        self.selections.insert(view_id, selection);
+    }
+
+    /// Applies a transaction to the document, accumulating its changes so that
+    /// [`Document::changes`] reflects everything applied since the last commit.
+    pub fn apply(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
+        let success = transaction.changes().apply(&mut self.text);
+        if success {
+            if let Some(selection) = transaction.selection() {
+                self.selections.insert(view_id, selection.clone());
+            } else if let Some(selection) = self.selections.get(&view_id) {
+                let selection = selection.clone().map(transaction.changes());
+                self.selections.insert(view_id, selection);
+            }
+            if let Some(snippet) = &mut self.active_snippet {
+                snippet.map(transaction.changes());
+            }
+            self.changes = self.changes.clone().compose(transaction.changes().clone());
+        }
+        success
+    }
+
+    /// The changes accumulated since the last commit to history.
+    #[inline]
+    pub fn changes(&self) -> &ChangeSet {
+        &self.changes
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language
+            .as_ref()
+            .map(|language| language.scope.as_str())
+    }
+
+    pub fn language_server(&self) -> Option<&helix_lsp::Client> {
+        self.language_server.as_deref()
+    }
+
+    /// Stores (or clears) the snippet being filled in.
+    pub fn set_active_snippet(&mut self, snippet: Option<SnippetState>) {
+        self.active_snippet = snippet;
+    }
+
+    /// The snippet currently being filled in, for tabstop navigation.
+    pub fn active_snippet_mut(&mut self) -> Option<&mut SnippetState> {
+        self.active_snippet.as_mut()
+    }
+}
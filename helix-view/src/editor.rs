@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use arc_swap::access::DynGuard;
+use arc_swap::ArcSwap;
+use serde::{Deserialize, Serialize};
+
+use crate::graphics::Rect;
+use crate::theme::Theme;
+use crate::tree::Tree;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct Config {
+    /// Number of lines of padding around the edge of the screen when scrolling.
+    pub scrolloff: usize,
+    /// Mouse support. Defaults to true.
+    pub mouse: bool,
+    /// Automatic insertion of pairs to parentheses, brackets, etc. Defaults to true.
+    pub auto_pairs: bool,
+    /// Automatic auto-completion, automatically pop up without user trigger. Defaults to true.
+    pub auto_completion: bool,
+    /// Time in milliseconds since last keypress before idle timers trigger.
+    pub idle_timeout: u64,
+    /// Minimum number of characters typed before completions are requested.
+    pub completion_trigger_len: u8,
+    /// Whether accepting a completion that provides both an `insert` and a
+    /// `replace` range should use the `replace` range (positionally, when the
+    /// cursor is at or past the identifier). Defaults to true; set to false to
+    /// always insert and never replace trailing text.
+    pub completion_replace: bool,
+    /// Whether typing an LSP commit character accepts the highlighted completion
+    /// and continues typing. Defaults to true; set to false to disable globally.
+    pub completion_commit_characters: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            scrolloff: 5,
+            mouse: true,
+            auto_pairs: true,
+            auto_completion: true,
+            idle_timeout: 400,
+            completion_trigger_len: 2,
+            completion_replace: true,
+            completion_commit_characters: true,
+        }
+    }
+}
+
+pub struct Editor {
+    pub tree: Tree,
+    pub theme: Theme,
+    pub syn_loader: Arc<helix_core::syntax::Loader>,
+    config: Arc<ArcSwap<Config>>,
+}
+
+impl Editor {
+    /// A snapshot of the current editor configuration.
+    pub fn config(&self) -> DynGuard<Config> {
+        self.config.load()
+    }
+
+    pub fn resize(&mut self, area: Rect) {
+        self.tree.resize(area);
+    }
+}
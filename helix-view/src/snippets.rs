@@ -0,0 +1,83 @@
+use helix_core::{ChangeSet, Range, Selection};
+
+/// The active snippet placed in a document after accepting a snippet
+/// completion. It keeps the ordered tabstops (each a group of linked ranges so
+/// same-numbered tabstops are edited simultaneously) together with the index of
+/// the tabstop the selection currently sits on. It is kept until `advance` is
+/// called past the final tabstop or another snippet replaces it; moving the
+/// cursor out of the snippet region does not clear it.
+#[derive(Debug, Clone)]
+pub struct SnippetState {
+    tabstops: Vec<Vec<Range>>,
+    active: usize,
+}
+
+impl SnippetState {
+    /// Creates the state for a freshly inserted snippet. `active` starts at `0`,
+    /// the first (lowest-numbered) tabstop; the caller places the selection
+    /// there from [`SnippetState::active_selection`].
+    pub fn new(tabstops: Vec<Vec<Range>>) -> Self {
+        Self {
+            tabstops,
+            active: 0,
+        }
+    }
+
+    /// The selection for the currently active tabstop, linking all ranges that
+    /// share its number.
+    pub fn active_selection(&self) -> Selection {
+        Selection::new(self.tabstops[self.active].clone().into(), 0)
+    }
+
+    /// Advances to the next tabstop and returns its selection, or `None` if the
+    /// final tabstop (`$0`) has already been reached, signalling the caller to
+    /// drop the snippet state.
+    pub fn advance(&mut self) -> Option<Selection> {
+        if self.active + 1 >= self.tabstops.len() {
+            return None;
+        }
+        self.active += 1;
+        Some(self.active_selection())
+    }
+
+    /// Maps every tabstop range over `changes` so the stored positions stay
+    /// valid as the user edits the placeholders.
+    pub fn map(&mut self, changes: &ChangeSet) {
+        for group in &mut self.tabstops {
+            for range in group.iter_mut() {
+                *range = range.map(changes);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn state() -> SnippetState {
+        // three tabstops: $1 (linked pair), $2, $0
+        SnippetState::new(vec![
+            vec![Range::new(1, 2), Range::new(5, 6)],
+            vec![Range::new(8, 8)],
+            vec![Range::new(10, 10)],
+        ])
+    }
+
+    #[test]
+    fn active_selection_links_shared_tabstops() {
+        let state = state();
+        assert_eq!(state.active_selection().len(), 2);
+    }
+
+    #[test]
+    fn advance_walks_tabstops_then_stops() {
+        let mut state = state();
+        // $1 -> $2
+        assert_eq!(state.advance().unwrap().len(), 1);
+        // $2 -> $0
+        assert_eq!(state.advance().unwrap().len(), 1);
+        // past $0
+        assert!(state.advance().is_none());
+    }
+}